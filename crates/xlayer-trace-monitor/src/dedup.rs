@@ -0,0 +1,69 @@
+use crate::{transaction::TransactionProcessId, utils::Hash32};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Mutex, PoisonError},
+};
+
+type EventKey = (Hash32, TransactionProcessId);
+
+/// Bounded LRU of recently seen transaction events.
+/// When full, the least recently seen event is evicted to make room.
+#[derive(Debug)]
+pub(crate) struct DedupWindow {
+    capacity: usize,
+    seen: Mutex<Seen>,
+}
+
+#[derive(Debug, Default)]
+struct Seen {
+    /// Last-seen tick of each event.
+    ticks: HashMap<EventKey, u64>,
+    /// Events ordered by last-seen tick, least recent first.
+    order: BTreeMap<u64, EventKey>,
+    next_tick: u64,
+}
+
+impl DedupWindow {
+    /// Create a window remembering up to `capacity` events. `capacity` must be non-zero.
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            seen: Mutex::new(Seen::default()),
+        }
+    }
+
+    /// Record an event as most recently seen. Returns `true` if it was already in the window
+    /// (i.e. a duplicate).
+    pub(crate) fn check_and_insert(
+        &self,
+        tx_hash: Hash32,
+        process_id: TransactionProcessId,
+    ) -> bool {
+        let key = (tx_hash, process_id);
+        let mut seen = self.seen.lock().unwrap_or_else(PoisonError::into_inner);
+        let tick = seen.next_tick;
+        seen.next_tick += 1;
+
+        if let Some(previous) = seen.ticks.insert(key, tick) {
+            seen.order.remove(&previous);
+            seen.order.insert(tick, key);
+            return true;
+        }
+
+        if seen.order.len() >= self.capacity
+            && let Some((_, oldest)) = seen.order.pop_first()
+        {
+            seen.ticks.remove(&oldest);
+        }
+        seen.order.insert(tick, key);
+        false
+    }
+
+    /// Forget an event, e.g. because its line could not be enqueued and was never logged.
+    pub(crate) fn remove(&self, tx_hash: Hash32, process_id: TransactionProcessId) {
+        let mut seen = self.seen.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(tick) = seen.ticks.remove(&(tx_hash, process_id)) {
+            seen.order.remove(&tick);
+        }
+    }
+}
@@ -1,13 +1,17 @@
 //! Transaction tracing: log transaction/block lifecycle to a file.
 //! Logging is non-blocking (bounded channel + writer thread).
+//! Lines can also be shipped to a remote TCP/UDP collector instead of a file.
 
+mod dedup;
+pub mod sink;
 pub mod tracer;
 pub mod transaction;
 pub mod utils;
 
+pub use sink::Sink;
 pub use tracer::{
-    TransactionTracer, flush_global_tracer, get_global_tracer, init_global_tracer,
-    sync_global_tracer,
+    TransactionTracer, TransactionTracerBuilder, flush_global_tracer, get_global_tracer,
+    init_global_tracer, sync_global_tracer,
 };
 pub use transaction::TransactionProcessId;
-pub use utils::{Hash32, format_hash_hex, from_b256};
+pub use utils::{Hash32, TimestampFormat, format_hash_hex, from_b256};
@@ -0,0 +1,202 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufWriter, Write},
+    net::{SocketAddr, TcpStream, UdpSocket},
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+/// Timeout for establishing a TCP connection to a remote collector.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Timeout for a single socket write, so a stalled collector cannot wedge the writer thread.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Minimum time between TCP reconnect attempts.
+/// Lines logged while disconnected are dropped.
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Destination for formatted trace lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Sink {
+    /// Append lines to a local file.
+    File(PathBuf),
+    /// Stream newline-delimited lines to a TCP collector, reconnecting on failure.
+    Tcp(SocketAddr),
+    /// Send each line as a single datagram (best-effort).
+    Udp(SocketAddr),
+}
+
+/// Writer-thread side of a [`Sink`].
+#[derive(Debug)]
+pub(crate) enum SinkWriter {
+    File(Option<BufWriter<File>>),
+    Tcp {
+        addr: SocketAddr,
+        stream: Option<BufWriter<TcpStream>>,
+        last_attempt: Instant,
+    },
+    Udp(Option<UdpSocket>),
+}
+
+impl SinkWriter {
+    /// Open the sink. Failures are logged; lines are then dropped (or, for TCP, retried later).
+    pub(crate) fn open(sink: Sink) -> Self {
+        match sink {
+            Sink::File(file_path) => Self::File(open_file(file_path)),
+            Sink::Tcp(addr) => Self::Tcp {
+                addr,
+                stream: connect_tcp(addr),
+                last_attempt: Instant::now(),
+            },
+            Sink::Udp(addr) => Self::Udp(bind_udp(addr)),
+        }
+    }
+
+    /// Write one line. Returns `Ok(false)` if the line was dropped because the sink is unavailable.
+    pub(crate) fn write_line(&mut self, line: &str) -> std::io::Result<bool> {
+        match self {
+            Self::File(None) | Self::Udp(None) => Ok(false),
+            Self::File(Some(writer)) => writeln!(writer, "{line}").map(|()| true),
+            Self::Tcp {
+                addr,
+                stream,
+                last_attempt,
+            } => {
+                if stream.is_none() && last_attempt.elapsed() >= RECONNECT_INTERVAL {
+                    *stream = connect_tcp(*addr);
+                    *last_attempt = Instant::now();
+                }
+                let Some(writer) = stream else {
+                    return Ok(false);
+                };
+                if let Err(e) = writeln!(writer, "{line}") {
+                    *stream = None;
+                    *last_attempt = Instant::now();
+                    return Err(e);
+                }
+                Ok(true)
+            }
+            Self::Udp(Some(socket)) => {
+                let mut datagram = Vec::with_capacity(line.len() + 1);
+                datagram.extend_from_slice(line.as_bytes());
+                datagram.push(b'\n');
+                // Best-effort: a failed send just drops the line.
+                Ok(socket.send(&datagram).is_ok())
+            }
+        }
+    }
+
+    /// Flush buffered lines to the OS (or the socket).
+    pub(crate) fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Self::File(Some(writer)) => writer.flush(),
+            Self::Tcp {
+                stream,
+                last_attempt,
+                ..
+            } => {
+                let Some(writer) = stream else {
+                    return Ok(());
+                };
+                let result = writer.flush();
+                if result.is_err() {
+                    *stream = None;
+                    *last_attempt = Instant::now();
+                }
+                result
+            }
+            Self::File(None) | Self::Udp(_) => Ok(()),
+        }
+    }
+
+    /// Flush and, for files, sync to disk.
+    pub(crate) fn sync_all(&mut self) -> std::io::Result<()> {
+        self.flush()?;
+        match self {
+            Self::File(Some(writer)) => writer.get_ref().sync_all(),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn open_file(file_path: PathBuf) -> Option<BufWriter<File>> {
+    // Create parent directories if they don't exist
+    if let Some(parent) = file_path.parent()
+        && let Err(e) = fs::create_dir_all(parent)
+    {
+        tracing::warn!(
+            target: "tx_trace",
+            ?parent,
+            error = %e,
+            "Failed to create transaction trace output directory"
+        );
+    }
+
+    match OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&file_path)
+    {
+        Ok(file) => {
+            tracing::info!(
+                target: "tx_trace",
+                ?file_path,
+                "Transaction trace file opened for appending"
+            );
+            Some(BufWriter::new(file))
+        }
+        Err(e) => {
+            tracing::warn!(
+                target: "tx_trace",
+                ?file_path,
+                error = %e,
+                "Failed to open transaction trace file"
+            );
+            None
+        }
+    }
+}
+
+fn connect_tcp(addr: SocketAddr) -> Option<BufWriter<TcpStream>> {
+    let stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT).and_then(|stream| {
+        stream
+            .set_write_timeout(Some(WRITE_TIMEOUT))
+            .map(|()| stream)
+    });
+    match stream {
+        Ok(stream) => {
+            tracing::info!(target: "tx_trace", %addr, "Connected to transaction trace collector");
+            Some(BufWriter::new(stream))
+        }
+        Err(e) => {
+            tracing::warn!(
+                target: "tx_trace",
+                %addr,
+                error = %e,
+                "Failed to connect to transaction trace collector"
+            );
+            None
+        }
+    }
+}
+
+fn bind_udp(addr: SocketAddr) -> Option<UdpSocket> {
+    let local: SocketAddr = if addr.is_ipv4() {
+        ([0, 0, 0, 0], 0).into()
+    } else {
+        ([0u16; 8], 0).into()
+    };
+    match UdpSocket::bind(local).and_then(|socket| socket.connect(addr).map(|()| socket)) {
+        Ok(socket) => Some(socket),
+        Err(e) => {
+            tracing::warn!(
+                target: "tx_trace",
+                %addr,
+                error = %e,
+                "Failed to open UDP socket for transaction trace collector"
+            );
+            None
+        }
+    }
+}
@@ -1,22 +1,29 @@
 use crate::{
+    dedup::DedupWindow,
+    sink::{Sink, SinkWriter},
     transaction::TransactionProcessId,
-    utils::{Hash32, current_timestamp_ms, format_csv_line, format_hash_hex},
+    utils::{Hash32, TimestampFormat, current_timestamp_ns, format_csv_line, format_hash_hex},
 };
 
-use crossbeam_channel::Sender;
+use crossbeam_channel::{Sender, TrySendError};
 use std::{
-    fs::{self, File, OpenOptions},
-    io::{BufWriter, Write},
     path::PathBuf,
-    sync::{Arc, OnceLock},
+    sync::{
+        Arc, OnceLock,
+        atomic::{AtomicU64, Ordering},
+    },
     thread,
     time::Instant,
 };
+use tokio::sync::oneshot;
 
 /// Capacity of the channel between log callers and the writer thread.
 /// When full, new log lines are dropped to avoid blocking the caller.
 const CHANNEL_CAPACITY: usize = 65_536;
 
+/// Default trace file path.
+const DEFAULT_OUTPUT_PATH: &str = "/data/logs/trace.log";
+
 /// Number of log entries to write before forcing a flush.
 /// This reduces system calls by batching writes through `BufWriter`.
 const FLUSH_INTERVAL_WRITES: u64 = 100;
@@ -59,8 +66,152 @@ pub fn sync_global_tracer() -> Result<(), std::io::Error> {
 #[derive(Debug)]
 enum WriterMessage {
     Line(String),
-    Flush(Option<Sender<Result<(), std::io::Error>>>),
-    SyncAll(Option<Sender<Result<(), std::io::Error>>>),
+    BlockLine(String),
+    Flush(Option<Ack>),
+    SyncAll(Option<Ack>),
+}
+
+/// Acknowledgment channel for flush/sync requests: blocking for the sync API, oneshot for async.
+#[derive(Debug)]
+enum Ack {
+    Blocking(Sender<Result<(), std::io::Error>>),
+    Async(oneshot::Sender<Result<(), std::io::Error>>),
+}
+
+impl Ack {
+    fn send(self, result: Result<(), std::io::Error>) {
+        match self {
+            Self::Blocking(tx) => {
+                let _ = tx.send(result);
+            }
+            Self::Async(tx) => {
+                let _ = tx.send(result);
+            }
+        }
+    }
+}
+
+/// Builder for [`TransactionTracer`].
+///
+/// Defaults: enabled, file sink at `/data/logs/trace.log` for both transaction and block events,
+/// channel capacity of 65536 lines, millisecond timestamps, no deduplication.
+#[derive(Debug, Clone)]
+pub struct TransactionTracerBuilder {
+    enabled: bool,
+    sink: Sink,
+    block_sink: Option<Sink>,
+    channel_capacity: usize,
+    timestamp_format: TimestampFormat,
+    dedup_window: usize,
+}
+
+impl Default for TransactionTracerBuilder {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            sink: Sink::File(PathBuf::from(DEFAULT_OUTPUT_PATH)),
+            block_sink: None,
+            channel_capacity: CHANNEL_CAPACITY,
+            timestamp_format: TimestampFormat::default(),
+            dedup_window: 0,
+        }
+    }
+}
+
+impl TransactionTracerBuilder {
+    /// Enable or disable tracing. A disabled tracer spawns no writer thread.
+    pub const fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Write to a file. A directory path (trailing separator, or no extension and not
+    /// existing) gets `trace.log` appended.
+    pub fn output_path(mut self, output_path: impl Into<PathBuf>) -> Self {
+        self.sink = Sink::File(resolve_file_path(output_path.into()));
+        self
+    }
+
+    /// Write to the given sink as-is.
+    pub fn sink(mut self, sink: Sink) -> Self {
+        self.sink = sink;
+        self
+    }
+
+    /// Write block events to a separate file instead of alongside transaction events.
+    /// The path is resolved like [`output_path`](Self::output_path); if it resolves to the
+    /// main output, both event types share it as by default.
+    pub fn block_output_path(mut self, block_output_path: impl Into<PathBuf>) -> Self {
+        self.block_sink = Some(Sink::File(resolve_file_path(block_output_path.into())));
+        self
+    }
+
+    /// Write block events to a separate sink instead of alongside transaction events.
+    pub fn block_sink(mut self, block_sink: Sink) -> Self {
+        self.block_sink = Some(block_sink);
+        self
+    }
+
+    /// Capacity of the channel to the writer thread. Lines logged while it is full are dropped.
+    pub const fn channel_capacity(mut self, channel_capacity: usize) -> Self {
+        self.channel_capacity = channel_capacity;
+        self
+    }
+
+    /// Format of the timestamp column.
+    pub const fn timestamp_format(mut self, timestamp_format: TimestampFormat) -> Self {
+        self.timestamp_format = timestamp_format;
+        self
+    }
+
+    /// Suppress transaction events whose `(tx_hash, process_id)` is among the `capacity` most
+    /// recently seen. A suppressed repeat still counts as seen.
+    /// Block events are never deduplicated. A `capacity` of 0 (the default) disables it.
+    pub const fn dedup_window(mut self, capacity: usize) -> Self {
+        self.dedup_window = capacity;
+        self
+    }
+
+    /// Build the tracer and install it as the global tracer, unless one is already installed.
+    /// First call wins; later calls are ignored without spawning a writer thread.
+    pub fn install_global(self) {
+        GLOBAL_TRACER.get_or_init(|| Arc::new(self.build()));
+    }
+
+    /// Build the tracer, spawning the writer thread if enabled.
+    pub fn build(self) -> TransactionTracer {
+        let (tx, rx) = crossbeam_channel::bounded(self.channel_capacity);
+        if self.enabled {
+            // Routing blocks to the main sink must share its writer, not open it a second time
+            let block_sink = self
+                .block_sink
+                .filter(|block_sink| *block_sink != self.sink);
+            let sink = self.sink;
+            thread::spawn(move || write_handle(rx, sink, block_sink));
+        }
+
+        TransactionTracer {
+            inner: Arc::new(TransactionTracerInner {
+                enabled: self.enabled,
+                tx,
+                timestamp_format: self.timestamp_format,
+                dedup: (self.dedup_window > 0).then(|| DedupWindow::new(self.dedup_window)),
+                suppressed_count: AtomicU64::new(0),
+            }),
+        }
+    }
+}
+
+/// Resolve a user-supplied output path to a file path.
+fn resolve_file_path(path: PathBuf) -> PathBuf {
+    if path.to_string_lossy().ends_with('/')
+        || path.to_string_lossy().ends_with('\\')
+        || (path.extension().is_none() && !path.exists())
+    {
+        path.join("trace.log")
+    } else {
+        path
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -72,26 +223,21 @@ impl TransactionTracer {
     /// Create a new tracer. Logs are sent to a writer thread via a bounded channel; callers never block.
     /// Default path: `/data/logs/trace.log`.
     pub fn new(enabled: bool, output_path: Option<PathBuf>) -> Self {
-        let default_path = PathBuf::from("/data/logs/trace.log");
-        let final_path = output_path.unwrap_or(default_path);
-
-        let file_path = if final_path.to_string_lossy().ends_with('/')
-            || final_path.to_string_lossy().ends_with('\\')
-            || (final_path.extension().is_none() && !final_path.exists())
-        {
-            final_path.join("trace.log")
-        } else {
-            final_path
-        };
-
-        let (tx, rx) = crossbeam_channel::bounded(CHANNEL_CAPACITY);
-        if enabled {
-            thread::spawn(move || write_handle(rx, file_path));
+        let mut builder = Self::builder().enabled(enabled);
+        if let Some(output_path) = output_path {
+            builder = builder.output_path(output_path);
         }
+        builder.build()
+    }
 
-        Self {
-            inner: Arc::new(TransactionTracerInner { enabled, tx }),
-        }
+    /// Start building a tracer. See [`TransactionTracerBuilder`] for defaults.
+    pub fn builder() -> TransactionTracerBuilder {
+        TransactionTracerBuilder::default()
+    }
+
+    /// Number of transaction events suppressed by the dedup window.
+    pub fn suppressed_count(&self) -> u64 {
+        self.inner.suppressed_count.load(Ordering::Relaxed)
     }
 
     /// Check if tracing is enabled
@@ -99,8 +245,16 @@ impl TransactionTracer {
         self.inner.enabled
     }
 
-    fn send_line(&self, csv_line: String) {
-        let _ = self.inner.tx.try_send(WriterMessage::Line(csv_line));
+    /// Enqueue a transaction line. Returns `false` if it was dropped.
+    fn send_line(&self, csv_line: String) -> bool {
+        self.inner
+            .tx
+            .try_send(WriterMessage::Line(csv_line))
+            .is_ok()
+    }
+
+    fn send_block_line(&self, csv_line: String) {
+        let _ = self.inner.tx.try_send(WriterMessage::BlockLine(csv_line));
     }
 
     /// Flush buffer to the OS. Use `sync_all()` for disk persistence.
@@ -113,7 +267,7 @@ impl TransactionTracer {
         if self
             .inner
             .tx
-            .send(WriterMessage::Flush(Some(ack_tx)))
+            .send(WriterMessage::Flush(Some(Ack::Blocking(ack_tx))))
             .is_err()
         {
             return Err(std::io::Error::other(
@@ -125,6 +279,12 @@ impl TransactionTracer {
             .map_err(|_| std::io::Error::other("Writer thread did not acknowledge flush request"))?
     }
 
+    /// Request a flush without waiting for it, e.g. at a block boundary.
+    /// Returns `false` if the request could not be enqueued (disabled, channel full or writer gone).
+    pub fn flush_if_pending(&self) -> bool {
+        self.is_enabled() && self.inner.tx.try_send(WriterMessage::Flush(None)).is_ok()
+    }
+
     /// Sync to disk. Call before shutdown to persist buffered data.
     pub fn sync_all(&self) -> Result<(), std::io::Error> {
         if !self.is_enabled() {
@@ -135,7 +295,7 @@ impl TransactionTracer {
         if self
             .inner
             .tx
-            .send(WriterMessage::SyncAll(Some(ack_tx)))
+            .send(WriterMessage::SyncAll(Some(Ack::Blocking(ack_tx))))
             .is_err()
         {
             return Err(std::io::Error::other(
@@ -147,10 +307,61 @@ impl TransactionTracer {
             .map_err(|_| std::io::Error::other("Writer thread did not acknowledge sync request"))?
     }
 
+    /// Async variant of [`flush`](Self::flush): awaits the writer thread's acknowledgment
+    /// instead of blocking on it.
+    pub async fn flush_async(&self) -> Result<(), std::io::Error> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+
+        let (ack_tx, ack_rx) = oneshot::channel();
+        self.send_async(WriterMessage::Flush(Some(Ack::Async(ack_tx))))
+            .await?;
+        ack_rx
+            .await
+            .map_err(|_| std::io::Error::other("Writer thread did not acknowledge flush request"))?
+    }
+
+    /// Async variant of [`sync_all`](Self::sync_all).
+    pub async fn sync_all_async(&self) -> Result<(), std::io::Error> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+
+        let (ack_tx, ack_rx) = oneshot::channel();
+        self.send_async(WriterMessage::SyncAll(Some(Ack::Async(ack_tx))))
+            .await?;
+        ack_rx
+            .await
+            .map_err(|_| std::io::Error::other("Writer thread did not acknowledge sync request"))?
+    }
+
+    /// Enqueue a message without blocking the async runtime.
+    /// If the channel is full, a helper thread does the blocking send and the task awaits it.
+    async fn send_async(&self, msg: WriterMessage) -> Result<(), std::io::Error> {
+        let disconnected =
+            || std::io::Error::other("Writer thread disconnected for transaction trace file");
+        let msg = match self.inner.tx.try_send(msg) {
+            Ok(()) => return Ok(()),
+            Err(TrySendError::Full(msg)) => msg,
+            Err(TrySendError::Disconnected(_)) => return Err(disconnected()),
+        };
+
+        let tx = self.inner.tx.clone();
+        let (sent_tx, sent_rx) = oneshot::channel();
+        thread::spawn(move || {
+            let _ = sent_tx.send(tx.send(msg).is_ok());
+        });
+        match sent_rx.await {
+            Ok(true) => Ok(()),
+            _ => Err(disconnected()),
+        }
+    }
+
     /// Log transaction event at current time point
     pub fn log_transaction(
         &self,
-        tx_hash: Hash32,
+        tx_hash: impl Into<Hash32>,
         process_id: TransactionProcessId,
         block_number: Option<u64>,
     ) {
@@ -158,18 +369,41 @@ impl TransactionTracer {
             return;
         }
 
-        let timestamp_ms = current_timestamp_ms();
+        let tx_hash = tx_hash.into();
+        if let Some(dedup) = &self.inner.dedup
+            && dedup.check_and_insert(tx_hash, process_id)
+        {
+            self.inner.suppressed_count.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+
+        let timestamp_ns = current_timestamp_ns();
         let trace_hash = format_hash_hex(&tx_hash);
 
-        let csv_line = format_csv_line(&trace_hash, process_id, timestamp_ms, None, block_number);
+        #[cfg(feature = "tracing-sink")]
+        emit_trace_event(&trace_hash, process_id, block_number);
 
-        self.send_line(csv_line);
+        let csv_line = format_csv_line(
+            &trace_hash,
+            process_id,
+            timestamp_ns,
+            self.inner.timestamp_format,
+            None,
+            block_number,
+        );
+
+        if !self.send_line(csv_line)
+            && let Some(dedup) = &self.inner.dedup
+        {
+            // Not logged, so a retry must not count as a duplicate
+            dedup.remove(tx_hash, process_id);
+        }
     }
 
     /// Log block event at current time point
     pub fn log_block(
         &self,
-        block_hash: Hash32,
+        block_hash: impl Into<Hash32>,
         block_number: u64,
         process_id: TransactionProcessId,
     ) {
@@ -177,24 +411,29 @@ impl TransactionTracer {
             return;
         }
 
-        let timestamp_ms = current_timestamp_ms();
+        let block_hash = block_hash.into();
+        let timestamp_ns = current_timestamp_ns();
         let trace_hash = format_hash_hex(&block_hash);
 
+        #[cfg(feature = "tracing-sink")]
+        emit_trace_event(&trace_hash, process_id, Some(block_number));
+
         let csv_line = format_csv_line(
             &trace_hash,
             process_id,
-            timestamp_ms,
+            timestamp_ns,
+            self.inner.timestamp_format,
             Some(block_hash),
             Some(block_number),
         );
 
-        self.send_line(csv_line);
+        self.send_block_line(csv_line);
     }
 
     /// Log block event with a given timestamp (e.g. when block building started but hash was not yet available).
     pub fn log_block_with_timestamp(
         &self,
-        block_hash: Hash32,
+        block_hash: impl Into<Hash32>,
         block_number: u64,
         process_id: TransactionProcessId,
         timestamp_ms: u128,
@@ -203,17 +442,23 @@ impl TransactionTracer {
             return;
         }
 
+        let block_hash = block_hash.into();
+        let timestamp_ns = timestamp_ms.saturating_mul(1_000_000);
         let trace_hash = format_hash_hex(&block_hash);
 
+        #[cfg(feature = "tracing-sink")]
+        emit_trace_event(&trace_hash, process_id, Some(block_number));
+
         let csv_line = format_csv_line(
             &trace_hash,
             process_id,
-            timestamp_ms,
+            timestamp_ns,
+            self.inner.timestamp_format,
             Some(block_hash),
             Some(block_number),
         );
 
-        self.send_line(csv_line);
+        self.send_block_line(csv_line);
     }
 }
 
@@ -221,91 +466,111 @@ impl TransactionTracer {
 struct TransactionTracerInner {
     enabled: bool,
     tx: Sender<WriterMessage>,
+    timestamp_format: TimestampFormat,
+    dedup: Option<DedupWindow>,
+    suppressed_count: AtomicU64,
 }
 
-fn write_handle(rx: crossbeam_channel::Receiver<WriterMessage>, file_path: PathBuf) {
-    // Create parent directories if they don't exist
-    if let Some(parent) = file_path.parent()
-        && let Err(e) = fs::create_dir_all(parent)
-    {
-        tracing::warn!(
-            target: "tx_trace",
-            ?parent,
-            error = %e,
-            "Failed to create transaction trace output directory"
-        );
-    }
+/// `tracing` target of trace events, kept apart from the crate's own `tx_trace` diagnostics.
+#[cfg(feature = "tracing-sink")]
+const TRACE_EVENT_TARGET: &str = "tx_trace::event";
+
+/// Emit a trace event as a structured `tracing` event, so it reaches any installed subscriber.
+#[cfg(feature = "tracing-sink")]
+fn emit_trace_event(trace_hash: &str, process_id: TransactionProcessId, block_number: Option<u64>) {
+    tracing::info!(
+        target: TRACE_EVENT_TARGET,
+        process_id = process_id.as_u64(),
+        process = process_id.as_str(),
+        hash = trace_hash,
+        block_number,
+        "Trace event"
+    );
+}
 
-    let mut writer_opt: Option<BufWriter<File>> = match OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(&file_path)
-    {
-        Ok(file) => {
-            tracing::info!(
-                target: "tx_trace",
-                ?file_path,
-                "Transaction trace file opened for appending"
-            );
-            Some(BufWriter::new(file))
-        }
-        Err(e) => {
-            tracing::warn!(
-                target: "tx_trace",
-                ?file_path,
-                error = %e,
-                "Failed to open transaction trace file"
-            );
-            None
-        }
-    };
+/// A writer-thread destination with its own flush bookkeeping.
+#[derive(Debug)]
+struct Output {
+    writer: SinkWriter,
+    write_count: u64,
+    last_flush_time: Instant,
+}
 
-    let mut write_count: u64 = 0;
-    let mut last_flush_time = Instant::now();
+impl Output {
+    fn open(sink: Sink) -> Self {
+        Self {
+            writer: SinkWriter::open(sink),
+            write_count: 0,
+            last_flush_time: Instant::now(),
+        }
+    }
 
-    while let Ok(msg) = rx.recv() {
-        match msg {
-            WriterMessage::Line(csv_line) => {
-                if let Some(ref mut writer) = writer_opt {
-                    if writeln!(writer, "{csv_line}").is_err() {
+    fn write_line(&mut self, csv_line: &str) {
+        match self.writer.write_line(csv_line) {
+            Ok(true) => {
+                self.write_count += 1;
+                let now = Instant::now();
+                let time_since_flush = now.duration_since(self.last_flush_time);
+                let should_flush = self.write_count.is_multiple_of(FLUSH_INTERVAL_WRITES)
+                    || time_since_flush.as_secs() >= FLUSH_INTERVAL_SECONDS;
+                if should_flush {
+                    if let Err(e) = self.writer.flush() {
                         tracing::warn!(
                             target: "tx_trace",
-                            "Failed to write to transaction trace file"
+                            error = %e,
+                            "Failed to flush transaction trace sink"
                         );
-                    } else {
-                        write_count += 1;
-                        let now = Instant::now();
-                        let time_since_flush = now.duration_since(last_flush_time);
-                        let should_flush = write_count.is_multiple_of(FLUSH_INTERVAL_WRITES)
-                            || time_since_flush.as_secs() >= FLUSH_INTERVAL_SECONDS;
-                        if should_flush {
-                            if writer.flush().is_err() {
-                                tracing::warn!(
-                                    target: "tx_trace",
-                                    "Failed to flush transaction trace file"
-                                );
-                            }
-                            last_flush_time = now;
-                        }
                     }
+                    self.last_flush_time = now;
                 }
             }
+            Ok(false) => {}
+            Err(e) => {
+                // For TCP sinks this is how a disconnect shows up; the writer reconnects later
+                tracing::warn!(
+                    target: "tx_trace",
+                    error = %e,
+                    "Failed to write to transaction trace sink"
+                );
+            }
+        }
+    }
+}
+
+fn write_handle(
+    rx: crossbeam_channel::Receiver<WriterMessage>,
+    sink: Sink,
+    block_sink: Option<Sink>,
+) {
+    let mut output = Output::open(sink);
+    // Block events share the main output unless a separate sink is configured.
+    let mut block_output = block_sink.map(Output::open);
+
+    while let Ok(msg) = rx.recv() {
+        match msg {
+            WriterMessage::Line(csv_line) => output.write_line(&csv_line),
+            WriterMessage::BlockLine(csv_line) => {
+                block_output
+                    .as_mut()
+                    .unwrap_or(&mut output)
+                    .write_line(&csv_line);
+            }
             WriterMessage::Flush(ack_tx) => {
-                let result = match &mut writer_opt {
-                    Some(writer) => writer.flush(),
-                    None => Ok(()),
-                };
-                if let Some(tx) = ack_tx {
-                    let _ = tx.send(result);
+                let result = output.writer.flush();
+                let block_result = block_output
+                    .as_mut()
+                    .map_or(Ok(()), |block| block.writer.flush());
+                if let Some(ack) = ack_tx {
+                    ack.send(result.and(block_result));
                 }
             }
             WriterMessage::SyncAll(ack_tx) => {
-                let result = match &mut writer_opt {
-                    Some(writer) => writer.flush().and_then(|()| writer.get_ref().sync_all()),
-                    None => Ok(()),
-                };
-                if let Some(tx) = ack_tx {
-                    let _ = tx.send(result);
+                let result = output.writer.sync_all();
+                let block_result = block_output
+                    .as_mut()
+                    .map_or(Ok(()), |block| block.writer.sync_all());
+                if let Some(ack) = ack_tx {
+                    ack.send(result.and(block_result));
                 }
             }
         }
@@ -325,6 +590,36 @@ mod tests {
         (tracer, temp_dir, log_path)
     }
 
+    /// Tracer without a writer thread; the test drains `rx` by hand.
+    fn setup_manual_tracer(
+        channel_capacity: usize,
+        dedup: Option<DedupWindow>,
+    ) -> (
+        TransactionTracer,
+        crossbeam_channel::Receiver<WriterMessage>,
+    ) {
+        let (tx, rx) = crossbeam_channel::bounded(channel_capacity);
+        let tracer = TransactionTracer {
+            inner: Arc::new(TransactionTracerInner {
+                enabled: true,
+                tx,
+                timestamp_format: TimestampFormat::default(),
+                dedup,
+                suppressed_count: AtomicU64::new(0),
+            }),
+        };
+        (tracer, rx)
+    }
+
+    fn setup_test_tracer_with(
+        builder: TransactionTracerBuilder,
+    ) -> (TransactionTracer, TempDir, PathBuf) {
+        let temp_dir = TempDir::new().unwrap();
+        let log_path = temp_dir.path().join("test_trace.log");
+        let tracer = builder.output_path(&log_path).build();
+        (tracer, temp_dir, log_path)
+    }
+
     #[test]
     fn test_tracer_initialization() {
         let (tracer, temp_dir, log_path) = setup_test_tracer(true);
@@ -401,6 +696,178 @@ mod tests {
         drop(temp_dir);
     }
 
+    #[test]
+    fn test_timestamp_formats() {
+        // 2023-11-14T22:13:20.123456789Z
+        let timestamp_ns = 1_700_000_000_123_456_789u128;
+        let time_column = |format| {
+            let line = format_csv_line(
+                "0x00",
+                TransactionProcessId::SeqReceiveTxEnd,
+                timestamp_ns,
+                format,
+                None,
+                None,
+            );
+            line.split(',').nth(11).unwrap().to_string()
+        };
+
+        assert_eq!(time_column(TimestampFormat::UnixMillis), "1700000000123");
+        assert_eq!(
+            time_column(TimestampFormat::UnixNanos),
+            "1700000000123456789"
+        );
+        assert_eq!(
+            time_column(TimestampFormat::Iso8601),
+            "2023-11-14T22:13:20.123Z"
+        );
+
+        assert_eq!(
+            TimestampFormat::Iso8601.format(0),
+            "1970-01-01T00:00:00.000Z"
+        );
+        // Leap day
+        assert_eq!(
+            TimestampFormat::Iso8601.format(951_782_400_000_000_000),
+            "2000-02-29T00:00:00.000Z"
+        );
+    }
+
+    #[test]
+    fn test_csv_escapes_carriage_return() {
+        let line = format_csv_line(
+            "a\rb",
+            TransactionProcessId::SeqReceiveTxEnd,
+            0,
+            TimestampFormat::UnixMillis,
+            None,
+            None,
+        );
+        assert!(line.starts_with("X Layer,\"a\rb\","));
+    }
+
+    #[test]
+    fn test_log_with_timestamp_format() {
+        let (tracer, temp_dir, log_path) = setup_test_tracer_with(
+            TransactionTracer::builder().timestamp_format(TimestampFormat::Iso8601),
+        );
+
+        tracer.log_block_with_timestamp(
+            [0x57; 32],
+            1,
+            TransactionProcessId::SeqBlockBuildStart,
+            1_700_000_000_123,
+        );
+        tracer.flush().unwrap();
+
+        // A huge caller-supplied timestamp must not panic
+        tracer.log_block_with_timestamp(
+            [0x58; 32],
+            2,
+            TransactionProcessId::SeqBlockBuildStart,
+            u128::MAX,
+        );
+        tracer.flush().unwrap();
+
+        let content = fs::read_to_string(&log_path).unwrap();
+        assert!(content.contains(",2023-11-14T22:13:20.123Z,"));
+        assert!(content.contains(",9999-12-31T23:59:59.999Z,"));
+
+        // temp_dir will be automatically cleaned up when it goes out of scope
+        drop(temp_dir);
+    }
+
+    #[test]
+    fn test_dedup_window() {
+        let (tracer, temp_dir, log_path) =
+            setup_test_tracer_with(TransactionTracer::builder().dedup_window(16));
+
+        let tx_hash = [0x42; 32];
+        tracer.log_transaction(tx_hash, TransactionProcessId::SeqReceiveTxEnd, None);
+        tracer.log_transaction(tx_hash, TransactionProcessId::SeqReceiveTxEnd, None);
+        // Same tx at a different stage is a distinct event
+        tracer.log_transaction(tx_hash, TransactionProcessId::SeqTxExecutionEnd, Some(1));
+        // Block events are never deduplicated
+        tracer.log_block([0x43; 32], 1, TransactionProcessId::SeqBlockBuildEnd);
+        tracer.log_block([0x43; 32], 1, TransactionProcessId::SeqBlockBuildEnd);
+        tracer.flush().unwrap();
+
+        let content = fs::read_to_string(&log_path).unwrap();
+        assert_eq!(content.matches("xlayer_seq_receive_tx").count(), 1);
+        assert_eq!(content.matches("xlayer_seq_package_tx").count(), 1);
+        assert_eq!(content.matches("xlayer_seq_end_block").count(), 2);
+        assert_eq!(tracer.suppressed_count(), 1);
+
+        // temp_dir will be automatically cleaned up when it goes out of scope
+        drop(temp_dir);
+    }
+
+    #[test]
+    fn test_dedup_window_evicts_oldest() {
+        let (tracer, temp_dir, log_path) =
+            setup_test_tracer_with(TransactionTracer::builder().dedup_window(1));
+
+        tracer.log_transaction([0x01; 32], TransactionProcessId::SeqReceiveTxEnd, None);
+        tracer.log_transaction([0x02; 32], TransactionProcessId::SeqReceiveTxEnd, None);
+        // [0x01; 32] was evicted by [0x02; 32], so it is logged again
+        tracer.log_transaction([0x01; 32], TransactionProcessId::SeqReceiveTxEnd, None);
+        tracer.flush().unwrap();
+
+        let content = fs::read_to_string(&log_path).unwrap();
+        assert_eq!(content.lines().count(), 3);
+        assert_eq!(tracer.suppressed_count(), 0);
+
+        // temp_dir will be automatically cleaned up when it goes out of scope
+        drop(temp_dir);
+    }
+
+    #[test]
+    fn test_dedup_window_refreshes_on_hit() {
+        let (tracer, temp_dir, log_path) =
+            setup_test_tracer_with(TransactionTracer::builder().dedup_window(2));
+        let log =
+            |byte| tracer.log_transaction([byte; 32], TransactionProcessId::SeqReceiveTxEnd, None);
+
+        log(0x01);
+        log(0x02);
+        // Hit: 0x01 becomes the most recently seen
+        log(0x01);
+        // Evicts 0x02, the least recently seen, not 0x01
+        log(0x03);
+        log(0x01);
+        log(0x02);
+        tracer.flush().unwrap();
+
+        let content = fs::read_to_string(&log_path).unwrap();
+        assert_eq!(content.lines().count(), 4);
+        assert_eq!(
+            content.matches(&format!("0x{}", "01".repeat(32))).count(),
+            1
+        );
+        assert_eq!(
+            content.matches(&format!("0x{}", "02".repeat(32))).count(),
+            2
+        );
+        assert_eq!(tracer.suppressed_count(), 2);
+
+        // temp_dir will be automatically cleaned up when it goes out of scope
+        drop(temp_dir);
+    }
+
+    #[test]
+    fn test_dedup_ignores_dropped_lines() {
+        let (tracer, rx) = setup_manual_tracer(1, Some(DedupWindow::new(16)));
+
+        tracer.log_transaction([0x01; 32], TransactionProcessId::SeqReceiveTxEnd, None);
+        // Channel full: dropped, so it must not be remembered as seen
+        tracer.log_transaction([0x02; 32], TransactionProcessId::SeqReceiveTxEnd, None);
+        assert_eq!(rx.try_iter().count(), 1);
+
+        tracer.log_transaction([0x02; 32], TransactionProcessId::SeqReceiveTxEnd, None);
+        assert_eq!(rx.try_iter().count(), 1);
+        assert_eq!(tracer.suppressed_count(), 0);
+    }
+
     #[test]
     fn test_multiple_process_ids() {
         let (tracer, temp_dir, log_path) = setup_test_tracer(true);
@@ -459,6 +926,73 @@ mod tests {
         drop(temp_dir);
     }
 
+    #[tokio::test]
+    async fn test_flush_async() {
+        let (tracer, temp_dir, log_path) = setup_test_tracer(true);
+
+        tracer.log_transaction([0x9b; 32], TransactionProcessId::SeqReceiveTxEnd, None);
+        tracer.flush_async().await.unwrap();
+
+        let content = fs::read_to_string(&log_path).unwrap();
+        assert!(content.contains("xlayer_seq_receive_tx"));
+
+        tracer.log_block([0x9c; 32], 1, TransactionProcessId::SeqBlockBuildEnd);
+        tracer.sync_all_async().await.unwrap();
+
+        let content = fs::read_to_string(&log_path).unwrap();
+        assert!(content.contains("xlayer_seq_end_block"));
+
+        // temp_dir will be automatically cleaned up when it goes out of scope
+        drop(temp_dir);
+    }
+
+    #[test]
+    fn test_flush_if_pending() {
+        let (tracer, temp_dir, log_path) = setup_test_tracer(true);
+
+        tracer.log_transaction([0x9d; 32], TransactionProcessId::SeqReceiveTxEnd, None);
+        assert!(tracer.flush_if_pending());
+
+        // The flush is not acknowledged, so poll for the line to reach the file
+        let deadline = Instant::now() + std::time::Duration::from_secs(5);
+        while fs::read_to_string(&log_path).unwrap_or_default().is_empty() {
+            assert!(Instant::now() < deadline, "flush request was not processed");
+            thread::sleep(std::time::Duration::from_millis(10));
+        }
+
+        let (disabled, _disabled_dir, _) = setup_test_tracer(false);
+        assert!(!disabled.flush_if_pending());
+
+        // temp_dir will be automatically cleaned up when it goes out of scope
+        drop(temp_dir);
+    }
+
+    #[tokio::test]
+    async fn test_flush_async_waits_when_channel_full() {
+        use std::{future::poll_fn, pin::pin, task::Poll};
+
+        let (tracer, rx) = setup_manual_tracer(1, None);
+        tracer.log_transaction([0x9e; 32], TransactionProcessId::SeqReceiveTxEnd, None);
+        assert!(rx.is_full());
+
+        // Channel full: the future must return `Pending` instead of blocking the runtime
+        let mut flush = pin!(tracer.flush_async());
+        assert!(poll_fn(|cx| Poll::Ready(flush.as_mut().poll(cx).is_pending())).await);
+
+        // Act as a slow writer thread: drain the line, then acknowledge the flush request
+        let writer = thread::spawn(move || {
+            thread::sleep(std::time::Duration::from_millis(50));
+            assert!(matches!(rx.recv(), Ok(WriterMessage::Line(_))));
+            let Ok(WriterMessage::Flush(Some(ack))) = rx.recv() else {
+                panic!("expected flush request");
+            };
+            ack.send(Ok(()));
+        });
+
+        flush.await.unwrap();
+        writer.join().unwrap();
+    }
+
     #[test]
     fn test_disabled_tracer_no_logging() {
         let (tracer, temp_dir, log_path) = setup_test_tracer(false);
@@ -479,6 +1013,291 @@ mod tests {
         drop(temp_dir);
     }
 
+    #[test]
+    fn test_log_alloy_b256() {
+        use alloy_primitives::B256;
+
+        let (tracer, temp_dir, log_path) = setup_test_tracer(true);
+
+        tracer.log_transaction(
+            B256::repeat_byte(0xde),
+            TransactionProcessId::SeqReceiveTxEnd,
+            None,
+        );
+        tracer.log_block(
+            B256::repeat_byte(0xef),
+            777,
+            TransactionProcessId::SeqBlockBuildEnd,
+        );
+        tracer.flush().unwrap();
+
+        let content = fs::read_to_string(&log_path).unwrap();
+        assert!(content.contains(&format!("0x{}", "de".repeat(32))));
+        assert!(content.contains(&format!("0x{}", "ef".repeat(32))));
+        assert!(content.contains("777"));
+
+        // temp_dir will be automatically cleaned up when it goes out of scope
+        drop(temp_dir);
+    }
+
+    #[cfg(feature = "tracing-sink")]
+    #[test]
+    fn test_tracing_sink_event_fields() {
+        use std::{collections::HashMap, sync::Mutex};
+        use tracing::{
+            Event, Metadata, Subscriber,
+            field::{Field, Visit},
+            span,
+        };
+
+        #[derive(Default)]
+        struct FieldVisitor(HashMap<String, String>);
+
+        impl Visit for FieldVisitor {
+            fn record_u64(&mut self, field: &Field, value: u64) {
+                self.0.insert(field.name().to_string(), value.to_string());
+            }
+
+            fn record_str(&mut self, field: &Field, value: &str) {
+                self.0.insert(field.name().to_string(), value.to_string());
+            }
+
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                self.0
+                    .insert(field.name().to_string(), format!("{value:?}"));
+            }
+        }
+
+        #[derive(Default)]
+        struct CaptureSubscriber {
+            events: Arc<Mutex<Vec<HashMap<String, String>>>>,
+        }
+
+        impl Subscriber for CaptureSubscriber {
+            fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+                metadata.target() == TRACE_EVENT_TARGET
+            }
+
+            fn new_span(&self, _span: &span::Attributes<'_>) -> span::Id {
+                span::Id::from_u64(1)
+            }
+
+            fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+
+            fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+            fn event(&self, event: &Event<'_>) {
+                let mut visitor = FieldVisitor::default();
+                event.record(&mut visitor);
+                self.events.lock().unwrap().push(visitor.0);
+            }
+
+            fn enter(&self, _span: &span::Id) {}
+
+            fn exit(&self, _span: &span::Id) {}
+        }
+
+        let subscriber = CaptureSubscriber::default();
+        let events = subscriber.events.clone();
+        let (tracer, temp_dir, _log_path) = setup_test_tracer(true);
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracer.log_transaction(
+                [0x11; 32],
+                TransactionProcessId::SeqTxExecutionEnd,
+                Some(42),
+            );
+            tracer.log_block([0x22; 32], 43, TransactionProcessId::SeqBlockBuildEnd);
+        });
+
+        let events = events.lock().unwrap();
+        let trace_events: Vec<_> = events
+            .iter()
+            .filter(|e| e.contains_key("process_id"))
+            .collect();
+        assert_eq!(trace_events.len(), 2);
+
+        assert_eq!(trace_events[0]["process_id"], "15034");
+        assert_eq!(trace_events[0]["process"], "xlayer_seq_package_tx");
+        assert_eq!(trace_events[0]["hash"], format!("0x{}", "11".repeat(32)));
+        assert_eq!(trace_events[0]["block_number"], "42");
+
+        assert_eq!(trace_events[1]["process_id"], "15036");
+        assert_eq!(trace_events[1]["hash"], format!("0x{}", "22".repeat(32)));
+        assert_eq!(trace_events[1]["block_number"], "43");
+
+        // temp_dir will be automatically cleaned up when it goes out of scope
+        drop(temp_dir);
+    }
+
+    #[test]
+    fn test_tcp_sink() {
+        use std::{
+            io::{BufRead, BufReader},
+            net::TcpListener,
+        };
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let tracer = TransactionTracer::builder()
+            .sink(Sink::Tcp(listener.local_addr().unwrap()))
+            .build();
+
+        tracer.log_transaction([0x01; 32], TransactionProcessId::RpcReceiveTxEnd, None);
+        tracer.log_block([0x02; 32], 7, TransactionProcessId::RpcBlockInsertEnd);
+        tracer.flush().unwrap();
+
+        let (stream, _) = listener.accept().unwrap();
+        let mut lines = BufReader::new(stream).lines();
+        assert!(
+            lines
+                .next()
+                .unwrap()
+                .unwrap()
+                .contains("xlayer_rpc_receive_tx")
+        );
+        assert!(
+            lines
+                .next()
+                .unwrap()
+                .unwrap()
+                .contains("xlayer_rpc_finish_block")
+        );
+    }
+
+    #[test]
+    fn test_udp_sink() {
+        use std::{net::UdpSocket, time::Duration};
+
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        socket
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let tracer = TransactionTracer::builder()
+            .sink(Sink::Udp(socket.local_addr().unwrap()))
+            .build();
+
+        tracer.log_transaction([0x03; 32], TransactionProcessId::SeqReceiveTxEnd, Some(9));
+        tracer.flush().unwrap();
+
+        let mut buf = [0u8; 4096];
+        let len = socket.recv(&mut buf).unwrap();
+        let datagram = std::str::from_utf8(&buf[..len]).unwrap();
+        assert!(datagram.ends_with('\n'));
+        assert!(datagram.contains("xlayer_seq_receive_tx"));
+    }
+
+    #[test]
+    fn test_tcp_sink_unreachable_does_not_block() {
+        // Bind then drop to get a local port with nothing listening.
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let tracer = TransactionTracer::builder().sink(Sink::Tcp(addr)).build();
+
+        tracer.log_transaction([0x04; 32], TransactionProcessId::SeqReceiveTxEnd, None);
+        assert!(tracer.flush().is_ok());
+    }
+
+    #[test]
+    fn test_builder() {
+        let temp_dir = TempDir::new().unwrap();
+        let tracer = TransactionTracer::builder()
+            .enabled(true)
+            .output_path(temp_dir.path().join("logs/"))
+            .channel_capacity(16)
+            .timestamp_format(TimestampFormat::UnixNanos)
+            .dedup_window(8)
+            .build();
+
+        assert!(tracer.is_enabled());
+        tracer.log_transaction([0x61; 32], TransactionProcessId::SeqReceiveTxEnd, None);
+        tracer.log_transaction([0x61; 32], TransactionProcessId::SeqReceiveTxEnd, None);
+        tracer.log_block_with_timestamp([0x62; 32], 5, TransactionProcessId::SeqBlockBuildStart, 7);
+        tracer.flush().unwrap();
+
+        // Directory path resolves to `trace.log` inside it
+        let content = fs::read_to_string(temp_dir.path().join("logs/trace.log")).unwrap();
+        assert_eq!(content.lines().count(), 2);
+        assert!(content.contains(",7000000,"));
+        assert_eq!(tracer.suppressed_count(), 1);
+
+        let disabled = TransactionTracer::builder().enabled(false).build();
+        assert!(!disabled.is_enabled());
+    }
+
+    #[test]
+    fn test_install_global() {
+        // The only test touching the global tracer, so the first install wins here
+        let temp_dir = TempDir::new().unwrap();
+        let log_path = temp_dir.path().join("global.log");
+        TransactionTracer::builder()
+            .output_path(&log_path)
+            .timestamp_format(TimestampFormat::Iso8601)
+            .install_global();
+        // Ignored: already installed
+        TransactionTracer::builder().enabled(false).install_global();
+
+        let tracer = get_global_tracer().unwrap();
+        assert!(tracer.is_enabled());
+        tracer.log_block_with_timestamp(
+            [0x81; 32],
+            1,
+            TransactionProcessId::SeqBlockBuildStart,
+            1_700_000_000_123,
+        );
+        flush_global_tracer().unwrap();
+
+        let content = fs::read_to_string(&log_path).unwrap();
+        assert!(content.contains(",2023-11-14T22:13:20.123Z,"));
+    }
+
+    #[test]
+    fn test_separate_block_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let tx_path = temp_dir.path().join("tx.log");
+        let block_path = temp_dir.path().join("block.log");
+        let tracer = TransactionTracer::builder()
+            .output_path(&tx_path)
+            .block_output_path(&block_path)
+            .build();
+
+        tracer.log_transaction([0x71; 32], TransactionProcessId::SeqReceiveTxEnd, None);
+        tracer.log_block([0x72; 32], 1, TransactionProcessId::SeqBlockBuildEnd);
+        tracer.log_block_with_timestamp([0x73; 32], 2, TransactionProcessId::SeqBlockBuildStart, 1);
+        tracer.log_transaction([0x74; 32], TransactionProcessId::SeqTxExecutionEnd, Some(2));
+        tracer.sync_all().unwrap();
+
+        let tx_content = fs::read_to_string(&tx_path).unwrap();
+        assert_eq!(tx_content.lines().count(), 2);
+        assert!(tx_content.lines().all(|line| line.contains("_tx,")));
+
+        let block_content = fs::read_to_string(&block_path).unwrap();
+        assert_eq!(block_content.lines().count(), 2);
+        assert!(block_content.lines().all(|line| line.contains("_block,")));
+    }
+
+    #[test]
+    fn test_block_file_same_as_output() {
+        let temp_dir = TempDir::new().unwrap();
+        let log_path = temp_dir.path().join("trace.log");
+        let tracer = TransactionTracer::builder()
+            .output_path(&log_path)
+            .block_output_path(&log_path)
+            .build();
+
+        for i in 0..500u64 {
+            tracer.log_transaction([0x75; 32], TransactionProcessId::SeqReceiveTxEnd, Some(i));
+            tracer.log_block([0x76; 32], i, TransactionProcessId::SeqBlockBuildEnd);
+        }
+        tracer.sync_all().unwrap();
+
+        // A single writer keeps every line intact
+        let content = fs::read_to_string(&log_path).unwrap();
+        assert_eq!(content.lines().count(), 1000);
+        assert!(content.lines().all(|line| line.split(',').count() == 23));
+    }
+
     #[test]
     fn test_default_path() {
         // Test that custom path logic works
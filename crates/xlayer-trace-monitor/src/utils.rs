@@ -18,16 +18,79 @@ pub fn format_hash_hex(hash: &Hash32) -> String {
     format!("0x{}", hex::encode(hash))
 }
 
-/// Convert from B256 (alloy-primitives) or any 32-byte array to Hash32
+/// Convert from B256 (alloy-primitives) or any 32-byte array to Hash32.
+///
+/// `B256` can also be passed directly to the tracer's logging methods, which accept
+/// `impl Into<Hash32>` (alloy-primitives provides `From<B256> for [u8; 32]`).
 pub fn from_b256(b256: impl AsRef<[u8; 32]>) -> Hash32 {
     *b256.as_ref()
 }
 
+/// Format of the timestamp column in trace lines.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimestampFormat {
+    /// Milliseconds since UNIX epoch (default).
+    #[default]
+    UnixMillis,
+    /// Nanoseconds since UNIX epoch.
+    UnixNanos,
+    /// UTC timestamp as `YYYY-MM-DDThh:mm:ss.sssZ`.
+    Iso8601,
+}
+
+impl TimestampFormat {
+    /// Format a timestamp given in nanoseconds since UNIX epoch.
+    pub fn format(&self, timestamp_ns: u128) -> String {
+        match self {
+            Self::UnixMillis => (timestamp_ns / 1_000_000).to_string(),
+            Self::UnixNanos => timestamp_ns.to_string(),
+            Self::Iso8601 => format_iso8601(timestamp_ns),
+        }
+    }
+}
+
+/// Latest instant representable as `YYYY-MM-DDThh:mm:ss.sssZ`: 9999-12-31T23:59:59.999Z.
+const MAX_ISO8601_MS: u128 = 253_402_300_799_999;
+
+/// Format nanoseconds since UNIX epoch as `YYYY-MM-DDThh:mm:ss.sssZ`.
+/// Timestamps past year 9999 are clamped to the latest representable instant.
+fn format_iso8601(timestamp_ns: u128) -> String {
+    let total_ms = (timestamp_ns / 1_000_000).min(MAX_ISO8601_MS);
+    let millis = total_ms % 1000;
+    let secs = total_ms / 1000;
+    let secs_of_day = secs % 86_400;
+    // Fits in i64 thanks to the clamp above
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{millis:03}Z",
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60
+    )
+}
+
+/// Convert days since UNIX epoch to a (year, month, day) civil date.
+/// See <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+const fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
 /// Format CSV line with 23 fields.
 pub(crate) fn format_csv_line(
     trace: &str,
     process_id: TransactionProcessId,
-    current_time: u128,
+    timestamp_ns: u128,
+    timestamp_format: TimestampFormat,
     block_hash: Option<Hash32>,
     block_number: Option<u64>,
 ) -> String {
@@ -36,7 +99,7 @@ pub(crate) fn format_csv_line(
             return Cow::Borrowed("");
         }
 
-        if s.contains(',') || s.contains('"') || s.contains('\n') {
+        if s.contains([',', '"', '\n', '\r']) {
             Cow::Owned(format!("\"{}\"", s.replace('"', "\"\"")))
         } else {
             Cow::Borrowed(s)
@@ -45,7 +108,7 @@ pub(crate) fn format_csv_line(
 
     // Pre-compute values that need conversion
     let process_str = process_id.as_u64().to_string();
-    let current_time_str = current_time.to_string();
+    let current_time_str = timestamp_format.format(timestamp_ns);
     let block_height = block_number.map(|n| n.to_string()).unwrap_or_default();
     let block_hash_str = block_hash.map(|h| format_hash_hex(&h)).unwrap_or_default();
 
@@ -78,10 +141,10 @@ pub(crate) fn format_csv_line(
     )
 }
 
-/// Get current timestamp in milliseconds since UNIX epoch
-pub(crate) fn current_timestamp_ms() -> u128 {
+/// Get current timestamp in nanoseconds since UNIX epoch
+pub(crate) fn current_timestamp_ns() -> u128 {
     std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap_or_default()
-        .as_millis()
+        .as_nanos()
 }